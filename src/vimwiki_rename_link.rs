@@ -3,18 +3,18 @@ use fehler::throws;
 use path_clean::PathClean;
 use pathdiff::diff_paths;
 use regex::{Captures, Regex};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs::{self, DirEntry};
+use std::io;
 use std::path::{Path, PathBuf};
-
-// Currently not support:
-// - Interwiki links
-// - Markdown reference-style links
+use std::process::Command;
 
 lazy_static! {
     static ref DEFAULT_LINK_RE: Regex = Regex::new(
         r"(?x)
         (?P<left>\[\[\s*)
-        ((?P<prefix>diary|file|local):)?(?P<path>(?-x:[^#|]+?))
+        ((?P<prefix>diary|file|local|wiki\d+|wn\.[A-Za-z0-9_]+):)?(?P<path>(?-x:[^#|]+?))
         (?P<right>(?-x:#.*)?(\|.*)*\]\])
     "
     )
@@ -22,7 +22,7 @@ lazy_static! {
     static ref WIKI_INCLUDE_RE: Regex = Regex::new(
         r"(?x)
         (?P<left>\{\{\s*)
-        ((?P<prefix>diary|file|local):)?(?P<path>(?-x:[^#|]+?))
+        ((?P<prefix>diary|file|local|wiki\d+|wn\.[A-Za-z0-9_]+):)?(?P<path>(?-x:[^#|]+?))
         (?P<right>(?-x:#.*)?(\|.*)*\}\})
     "
     )
@@ -30,11 +30,120 @@ lazy_static! {
     static ref MD_LINK_RE: Regex = Regex::new(
         r"(?x)
         (?P<left>\[.*\]\()
-        ((?P<prefix>diary|file|local):)?(?P<path>(?-x:[^#|]+?))
+        ((?P<prefix>diary|file|local|wiki\d+|wn\.[A-Za-z0-9_]+):)?(?P<path>(?-x:[^#|]+?))
         (?P<right>(?-x:#.*)?\))
     "
     )
     .unwrap();
+    // A reference-style link definition, e.g. `[id]: path "title"`. The
+    // label usage (`[text][id]` / shortcut `[id]` / collapsed `[id][]`)
+    // carries no path of its own, so only definition lines need rewriting.
+    static ref MD_LINK_REF_RE: Regex = Regex::new(
+        r"(?xm)
+        (?P<left>^\s*\[(?-x:[^\]]+)\]:\s*)
+        ((?P<prefix>diary|file|local|wiki\d+|wn\.[A-Za-z0-9_]+):)?(?P<path>(?-x:[^#\s]+))
+        (?P<right>(?-x:#\S*)?(?-x:.*))
+    "
+    )
+    .unwrap();
+}
+
+/// A single entry in a `WikiRegistry`: the root directory vimwiki resolves
+/// its links against and the file extension it expects pages to carry.
+struct WikiEntry {
+    root: PathBuf,
+    extension: String,
+    /// Whether this wiki's link style requires the extension to be spelled
+    /// out in emitted links (vimwiki's `markdown` syntax) or whether it's
+    /// conventionally omitted (vimwiki's default `wiki` syntax).
+    requires_link_extension: bool,
+}
+
+/// The set of wikis known to the current session, indexed both by position
+/// (`[[wiki1:Some Page]]`) and by name (`[[wn.MyWiki:Some Page]]`), mirroring
+/// vimwiki's own `g:vimwiki_list` configuration.
+#[derive(Default)]
+pub struct WikiRegistry {
+    entries: Vec<WikiEntry>,
+    names: HashMap<String, usize>,
+}
+
+impl WikiRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a wiki and returns its numeric index, usable as `wikiN`.
+    pub fn register<P: Into<PathBuf>>(
+        &mut self,
+        name: Option<&str>,
+        root: P,
+        extension: &str,
+        requires_link_extension: bool,
+    ) -> usize {
+        let index = self.entries.len();
+        self.entries.push(WikiEntry {
+            root: root.into(),
+            extension: extension.to_owned(),
+            requires_link_extension,
+        });
+        if let Some(name) = name {
+            self.names.insert(name.to_owned(), index);
+        }
+        index
+    }
+
+    /// The distinct extensions across every registered wiki, used to decide
+    /// whether an extension-less link plausibly refers to a known page.
+    fn known_extensions(&self) -> Vec<String> {
+        let mut extensions: Vec<String> = Vec::new();
+        for entry in &self.entries {
+            if !extensions.contains(&entry.extension) {
+                extensions.push(entry.extension.clone());
+            }
+        }
+        extensions
+    }
+
+    fn get(&self, index: usize) -> Option<&WikiEntry> {
+        self.entries.get(index)
+    }
+
+    fn get_by_name(&self, name: &str) -> Option<&WikiEntry> {
+        self.names.get(name).and_then(|&index| self.entries.get(index))
+    }
+
+    fn name_of(&self, index: usize) -> Option<&str> {
+        self.names
+            .iter()
+            .find(|(_, &i)| i == index)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Finds the registered wiki that owns `path`, preferring the most
+    /// specific (longest) root when wikis are nested inside one another.
+    fn find_owning(&self, path: &Path) -> Option<(usize, &WikiEntry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| path.starts_with(&entry.root))
+            .max_by_key(|(_, entry)| entry.root.as_os_str().len())
+    }
+}
+
+/// A numbered (`wikiN`) or named (`wn.Name`) interwiki prefix, parsed out of
+/// a link's `prefix` capture.
+enum InterwikiRef<'a> {
+    Index(usize),
+    Name(&'a str),
+}
+
+fn parse_interwiki_prefix(prefix: &str) -> Option<InterwikiRef> {
+    if let Some(rest) = prefix.strip_prefix("wiki") {
+        rest.parse::<usize>().ok().map(InterwikiRef::Index)
+    } else {
+        prefix.strip_prefix("wn.").map(InterwikiRef::Name)
+    }
 }
 
 #[allow(dead_code)]
@@ -62,19 +171,51 @@ impl AbsolutePath {
         &self.path
     }
 
-    fn get_file_name(&self) -> Option<String> {
-        self.path
-            .with_extension("")
-            .file_name()
-            .and_then(|x| x.to_str().map(String::from))
+    fn get_file_name(&self, keep_extension: bool) -> Option<String> {
+        let path = if keep_extension {
+            self.path.clone()
+        } else {
+            self.path.with_extension("")
+        };
+        path.file_name().and_then(|x| x.to_str().map(String::from))
+    }
+
+    /// Whether this path resolves to an existing file, trying
+    /// `fallback_extension` when the path itself carries none.
+    fn exists(&self, fallback_extension: Option<&str>) -> bool {
+        if self.path.extension().is_some() {
+            return self.path.is_file();
+        }
+        self.path.is_file()
+            || fallback_extension
+                .map(|ext| self.path.with_extension(ext).is_file())
+                .unwrap_or(false)
+    }
+
+    /// The extension-stripped path used as the key in the backlink index, so
+    /// that links written with and without an extension land on the same
+    /// page. This is coarser than `AbsolutePath::matches`: any extension is
+    /// stripped, known or not, so `link.md` and `link.wiki` share one key.
+    fn canonical_key(&self) -> PathBuf {
+        self.path.with_extension("")
     }
-}
 
-impl PartialEq for AbsolutePath {
-    fn eq(&self, other: &Self) -> bool {
+    /// Whether `self` and `other` refer to the same page. When both carry an
+    /// extension they must match exactly; when only one does, the bare path
+    /// is treated as a match only if the other's extension is one of this
+    /// wiki's `known_extensions` (so a link to an image or other non-page
+    /// file with the same stem isn't mistaken for the page).
+    fn matches(&self, other: &Self, known_extensions: &[String]) -> bool {
+        let is_known = |ext: &std::ffi::OsStr| {
+            ext.to_str()
+                .map(|ext| known_extensions.iter().any(|known| known == ext))
+                .unwrap_or(false)
+        };
         match (self.path.extension(), other.path.extension()) {
             (Some(_), Some(_)) => self.path == other.path,
-            _ => self.path.with_extension("") == other.path.with_extension(""),
+            (None, Some(ext)) => self.path == other.path.with_extension("") && is_known(ext),
+            (Some(ext), None) => self.path.with_extension("") == other.path && is_known(ext),
+            (None, None) => self.path == other.path,
         }
     }
 }
@@ -104,38 +245,81 @@ impl<'a> Link<'a> {
 struct Wiki<'a> {
     wiki_root: &'a Path,
     content_path: &'a Path,
+    registry: &'a WikiRegistry,
 }
 
 impl<'a> Wiki<'a> {
-    fn new(wiki_root: &'a Path, content_path: &'a Path) -> Self {
+    fn new(wiki_root: &'a Path, content_path: &'a Path, registry: &'a WikiRegistry) -> Self {
         Wiki {
             wiki_root,
             content_path,
+            registry,
         }
     }
 
     fn get_absolute_path(&self, link: &Link) -> AbsolutePath {
         let link_path = link.path.trim_start_matches('/');
-        let path = match link.prefix {
-            Some("diary") => self.wiki_root.join("diary").join(link_path),
-            _ => {
-                if link.path.starts_with('/') {
-                    self.wiki_root.join(link_path)
-                } else {
-                    self.content_path
-                        .parent()
-                        .expect("get_absolute_path: Wiki file should have a parent")
-                        .join(link_path)
-                }
+        let path = match link.prefix.and_then(parse_interwiki_prefix) {
+            Some(interwiki) => {
+                let entry = match interwiki {
+                    InterwikiRef::Index(index) => self.registry.get(index),
+                    InterwikiRef::Name(name) => self.registry.get_by_name(name),
+                };
+                entry
+                    .map(|entry| entry.root.join(link_path))
+                    .unwrap_or_else(|| self.wiki_root.join(link_path))
             }
+            None => match link.prefix {
+                Some("diary") => self.wiki_root.join("diary").join(link_path),
+                _ => {
+                    if link.path.starts_with('/') {
+                        self.wiki_root.join(link_path)
+                    } else {
+                        self.content_path
+                            .parent()
+                            .expect("get_absolute_path: Wiki file should have a parent")
+                            .join(link_path)
+                    }
+                }
+            },
         };
         AbsolutePath::new(path)
     }
 
+    /// The extensions this wiki recognizes as page content, used to decide
+    /// whether an extension-less link plausibly refers to a known page.
+    /// Falls back to the current file's own extension when no wiki has been
+    /// registered, preserving single-wiki behavior without any config.
+    fn known_extensions(&self) -> Vec<String> {
+        let extensions = self.registry.known_extensions();
+        if !extensions.is_empty() {
+            return extensions;
+        }
+        self.content_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| vec![ext.to_owned()])
+            .unwrap_or_default()
+    }
+
+    /// Whether `wiki_root`'s own link style spells out the extension (e.g.
+    /// vimwiki's `markdown` syntax) rather than omitting it (the default
+    /// `wiki` syntax). Unregistered wikis keep the legacy no-extension style.
+    fn requires_link_extension(&self, wiki_root: &Path) -> bool {
+        self.registry
+            .find_owning(wiki_root)
+            .map(|(_, entry)| entry.requires_link_extension)
+            .unwrap_or(false)
+    }
+
     fn get_relative_path(&self, to: &AbsolutePath) -> Option<String> {
-        // Strip file extension
+        let target = if self.requires_link_extension(self.wiki_root) {
+            to.get_path().to_path_buf()
+        } else {
+            to.get_path().with_extension("")
+        };
         diff_paths(
-            to.get_path().with_extension(""),
+            target,
             &self
                 .content_path
                 .parent()
@@ -151,7 +335,10 @@ impl<'a> Wiki<'a> {
             let path = caps.name("path").expect("Should captured with name link");
             let link = Link::new(prefix, path.as_str());
 
-            if &self.get_absolute_path(&link) != from {
+            if !self
+                .get_absolute_path(&link)
+                .matches(from, &self.known_extensions())
+            {
                 return origin;
             }
 
@@ -165,14 +352,39 @@ impl<'a> Wiki<'a> {
                 .as_str();
 
             let replaced = if to.is_in_diary() {
-                to.get_file_name()
+                let diary_root = self.wiki_root.join("diary");
+                to.get_file_name(self.requires_link_extension(&diary_root))
                     .map(|file_name| format!("diary:{}", file_name))
                     .unwrap_or_else(|| link.display())
+            } else if let Some((index, entry)) = self
+                .registry
+                .find_owning(to.get_path())
+                .filter(|(_, entry)| entry.root != self.wiki_root)
+            {
+                let target = if entry.requires_link_extension {
+                    to.get_path().to_path_buf()
+                } else {
+                    to.get_path().with_extension("")
+                };
+                diff_paths(target, &entry.root)
+                    .and_then(|p| p.to_str().map(String::from))
+                    .map(|relative_path| {
+                        let wiki_prefix = self
+                            .registry
+                            .name_of(index)
+                            .map(|name| format!("wn.{}", name))
+                            .unwrap_or_else(|| format!("wiki{}", index));
+                        format!("{}:{}", wiki_prefix, relative_path)
+                    })
+                    .unwrap_or_else(|| link.display())
             } else {
                 self.get_relative_path(&to)
                     .map(|relative_path| {
+                        // `wikiN`/`wn.*` only make sense pointing at a
+                        // foreign wiki; the target is now local, so only
+                        // `file:`/`local:` survive onto the relative link.
                         prefix
-                            .filter(|s| *s != "diary")
+                            .filter(|s| *s != "diary" && parse_interwiki_prefix(s).is_none())
                             .map(|s| format!("{}:{}", s, relative_path))
                             .unwrap_or_else(|| relative_path.to_owned())
                     })
@@ -191,7 +403,8 @@ impl<'a> Wiki<'a> {
         };
         let content = MD_LINK_RE.replace_all(content, replace);
         let content = DEFAULT_LINK_RE.replace_all(&content, replace);
-        WIKI_INCLUDE_RE.replace_all(&content, replace).into_owned()
+        let content = WIKI_INCLUDE_RE.replace_all(&content, replace);
+        MD_LINK_REF_RE.replace_all(&content, replace).into_owned()
     }
 
     #[throws]
@@ -200,6 +413,109 @@ impl<'a> Wiki<'a> {
         let updated_content = self.replace_links(&content, from, to);
         fs::write(self.content_path, updated_content)?;
     }
+
+    /// The line-level changes `update_links` would make, without writing
+    /// anything to disk.
+    #[throws]
+    fn diff_links(&self, from: &AbsolutePath, to: &AbsolutePath) -> Vec<LinkChange> {
+        let content = fs::read_to_string(self.content_path)?;
+        let updated_content = self.replace_links(&content, from, to);
+        content
+            .lines()
+            .zip(updated_content.lines())
+            .filter(|(old_line, new_line)| old_line != new_line)
+            .map(|(old_line, new_line)| LinkChange {
+                old_line: old_line.to_owned(),
+                new_line: new_line.to_owned(),
+            })
+            .collect()
+    }
+
+    /// The extension this wiki's own pages are expected to carry, used as a
+    /// fallback when a link omits its extension.
+    fn link_extension(&self) -> Option<&str> {
+        self.registry
+            .find_owning(self.wiki_root)
+            .map(|(_, entry)| entry.extension.as_str())
+    }
+
+    /// The extension to assume for an extension-less link resolved to
+    /// `resolved_path`: the extension of whichever registered wiki owns that
+    /// path (so e.g. diary pages use the diary wiki's own extension), this
+    /// file's own wiki as a fallback when `resolved_path` isn't inside any
+    /// registered wiki, or finally this file's own extension when no wiki
+    /// has been registered at all.
+    fn fallback_extension_for(&self, resolved_path: &Path) -> Option<String> {
+        self.registry
+            .find_owning(resolved_path)
+            .map(|(_, entry)| entry.extension.clone())
+            .or_else(|| self.link_extension().map(String::from))
+            .or_else(|| {
+                self.content_path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(String::from)
+            })
+    }
+
+    #[throws]
+    fn check_links(&self) -> Vec<BrokenLink> {
+        let content = fs::read_to_string(self.content_path)?;
+        let mut broken = Vec::new();
+        for re in &[&*DEFAULT_LINK_RE, &*WIKI_INCLUDE_RE, &*MD_LINK_RE, &*MD_LINK_REF_RE] {
+            for caps in re.captures_iter(&content) {
+                let raw_link = caps[0].to_owned();
+                let prefix = caps.name("prefix").map(|m| m.as_str());
+                let path = caps.name("path").expect("Should captured with name link");
+                if is_external_link(path.as_str()) {
+                    continue;
+                }
+                let link = Link::new(prefix, path.as_str());
+                let resolved = self.get_absolute_path(&link);
+                let fallback_extension = self.fallback_extension_for(resolved.get_path());
+                if !resolved.exists(fallback_extension.as_deref()) {
+                    broken.push(BrokenLink {
+                        source_file: self.content_path.to_path_buf(),
+                        raw_link,
+                        resolved_path: resolved.get_path().to_path_buf(),
+                    });
+                }
+            }
+        }
+        broken
+    }
+
+    /// The canonical keys of every link target this file references,
+    /// regardless of whether those targets resolve to existing files.
+    #[throws]
+    fn linked_targets(&self) -> Vec<PathBuf> {
+        let content = fs::read_to_string(self.content_path)?;
+        let mut targets = Vec::new();
+        for re in &[&*DEFAULT_LINK_RE, &*WIKI_INCLUDE_RE, &*MD_LINK_RE, &*MD_LINK_REF_RE] {
+            for caps in re.captures_iter(&content) {
+                let prefix = caps.name("prefix").map(|m| m.as_str());
+                let path = caps.name("path").expect("Should captured with name link");
+                if is_external_link(path.as_str()) {
+                    continue;
+                }
+                let link = Link::new(prefix, path.as_str());
+                targets.push(self.get_absolute_path(&link).canonical_key());
+            }
+        }
+        targets
+    }
+}
+
+/// A link found while auditing the wiki whose target does not resolve to an
+/// existing file, e.g. `PageMissing` in riki.
+pub struct BrokenLink {
+    pub source_file: PathBuf,
+    pub raw_link: String,
+    pub resolved_path: PathBuf,
+}
+
+fn is_external_link(path: &str) -> bool {
+    path.contains("://")
 }
 
 // one possible implementation of walking a directory only visiting files
@@ -209,6 +525,16 @@ fn visit_dirs(dir: &Path, cb: &dyn Fn(&DirEntry)) {
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
+            // Skip dotfiles/dotdirs such as `.git` so repo bookkeeping is
+            // never mistaken for wiki content.
+            let is_hidden = entry
+                .file_name()
+                .to_str()
+                .map(|name| name.starts_with('.'))
+                .unwrap_or(false);
+            if is_hidden {
+                continue;
+            }
             if path.is_dir() {
                 visit_dirs(&path, cb)?;
             } else {
@@ -218,18 +544,181 @@ fn visit_dirs(dir: &Path, cb: &dyn Fn(&DirEntry)) {
     }
 }
 
-pub fn rename(wiki_root: PathBuf, from: &str, to: &str) {
+/// Maps each link target's canonical key to every file that references it,
+/// built by scanning the whole wiki once.
+type BacklinkIndex = HashMap<PathBuf, Vec<PathBuf>>;
+
+fn build_backlink_index(wiki_root: &Path, registry: &WikiRegistry) -> BacklinkIndex {
+    let index: RefCell<BacklinkIndex> = RefCell::new(HashMap::new());
+    let visit = |entry: &DirEntry| {
+        let content_path = entry.path();
+        let wiki = Wiki::new(wiki_root, &content_path, registry);
+        match wiki.linked_targets() {
+            Ok(targets) => {
+                let mut index = index.borrow_mut();
+                for target in targets {
+                    let sources = index.entry(target).or_insert_with(Vec::new);
+                    if !sources.contains(&content_path) {
+                        sources.push(content_path.clone());
+                    }
+                }
+            }
+            Err(e) => panic!("Scan wiki {} failed: {}", content_path.display(), e),
+        }
+    };
+    visit_dirs(wiki_root, &visit);
+    index.into_inner()
+}
+
+/// The files that link to `page` (a path relative to `wiki_root`, as it
+/// would appear in a `[[...]]` link).
+pub fn backlinks(wiki_root: &Path, page: &str, registry: &WikiRegistry) -> Vec<PathBuf> {
+    let index = build_backlink_index(wiki_root, registry);
+    let key = AbsolutePath::new(wiki_root.join(page)).canonical_key();
+    index.get(&key).cloned().unwrap_or_default()
+}
+
+/// A single link line changed by a rename, before and after rewriting.
+pub struct LinkChange {
+    pub old_line: String,
+    pub new_line: String,
+}
+
+/// The changes a rename would make to one file, without having made them.
+pub struct FileDiff {
+    pub source_file: PathBuf,
+    pub changes: Vec<LinkChange>,
+}
+
+/// Options controlling how `rename` performs the move and stages its edits.
+pub struct RenameOptions {
+    /// Move the file with `git mv` and stage rewritten files with `git add`
+    /// when `wiki_root` is inside a git repository.
+    pub use_git: bool,
+    /// Don't move the file or write any changes; instead return the diffs
+    /// `rename` would otherwise apply, so callers can inspect the blast
+    /// radius of a rename before committing to it.
+    pub dry_run: bool,
+}
+
+impl Default for RenameOptions {
+    fn default() -> Self {
+        Self {
+            use_git: false,
+            dry_run: false,
+        }
+    }
+}
+
+fn is_git_repo(dir: &Path) -> bool {
+    Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(dir)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+fn move_file(wiki_root: &Path, from: &Path, to: &Path, use_git: bool) -> io::Result<()> {
+    if use_git {
+        let status = Command::new("git")
+            .arg("mv")
+            .arg(from)
+            .arg(to)
+            .current_dir(wiki_root)
+            .status()?;
+        if status.success() {
+            return Ok(());
+        }
+    }
+    fs::rename(from, to)
+}
+
+fn stage_file(wiki_root: &Path, content_path: &Path) {
+    let status = Command::new("git")
+        .arg("add")
+        .arg(content_path)
+        .current_dir(wiki_root)
+        .status();
+    match status {
+        Ok(status) if status.success() => (),
+        Ok(status) => panic!("git add {} exited with {}", content_path.display(), status),
+        Err(e) => panic!("git add {} failed: {}", content_path.display(), e),
+    }
+}
+
+pub fn rename(
+    wiki_root: PathBuf,
+    from: &str,
+    to: &str,
+    registry: &WikiRegistry,
+    options: &RenameOptions,
+) -> Vec<FileDiff> {
     let from_path = AbsolutePath::new(from);
     let to_path = AbsolutePath::new(to);
-    let update_links = |entry: &DirEntry| {
-        let content_path = entry.path();
-        let wiki = Wiki::new(&wiki_root, &content_path);
+
+    if options.dry_run {
+        let index = build_backlink_index(&wiki_root, registry);
+        let referencing_files = index.get(&from_path.canonical_key());
+        return referencing_files
+            .into_iter()
+            .flatten()
+            .filter_map(|content_path| {
+                let wiki = Wiki::new(&wiki_root, content_path, registry);
+                match wiki.diff_links(&from_path, &to_path) {
+                    Ok(changes) if !changes.is_empty() => Some(FileDiff {
+                        source_file: content_path.to_path_buf(),
+                        changes,
+                    }),
+                    Ok(_) => None,
+                    Err(e) => panic!("Diff wiki {} failed: {}", content_path.display(), e),
+                }
+            })
+            .collect();
+    }
+
+    let use_git = options.use_git && is_git_repo(&wiki_root);
+
+    if let Err(e) = move_file(&wiki_root, from_path.get_path(), to_path.get_path(), use_git) {
+        panic!(
+            "Move {} to {} failed: {}",
+            from_path.get_path().display(),
+            to_path.get_path().display(),
+            e
+        );
+    }
+
+    let index = build_backlink_index(&wiki_root, registry);
+    let referencing_files = index.get(&from_path.canonical_key());
+    for content_path in referencing_files.into_iter().flatten() {
+        let wiki = Wiki::new(&wiki_root, content_path, registry);
         match wiki.update_links(&from_path, &to_path) {
-            Ok(_) => (),
+            Ok(_) => {
+                if use_git {
+                    stage_file(&wiki_root, content_path);
+                }
+            }
             Err(e) => panic!("Update wiki {} failed: {}", content_path.display(), e),
         }
+    }
+
+    Vec::new()
+}
+
+/// Walks every file in `wiki_root` and reports links whose target does not
+/// exist on disk, without touching any file.
+pub fn check(wiki_root: PathBuf, registry: &WikiRegistry) -> Vec<BrokenLink> {
+    let broken = RefCell::new(Vec::new());
+    let check_links = |entry: &DirEntry| {
+        let content_path = entry.path();
+        let wiki = Wiki::new(&wiki_root, &content_path, registry);
+        match wiki.check_links() {
+            Ok(mut found) => broken.borrow_mut().append(&mut found),
+            Err(e) => panic!("Check wiki {} failed: {}", content_path.display(), e),
+        }
     };
-    visit_dirs(&wiki_root, &update_links);
+    visit_dirs(&wiki_root, &check_links);
+    broken.into_inner()
 }
 
 #[cfg(test)]
@@ -317,6 +806,45 @@ mod test_links_regex {
             .unwrap();
         assert_eq!(&cap["path"], "URL");
     }
+    #[test]
+    fn it_capture_numbered_interwiki_link() {
+        let cap = DEFAULT_LINK_RE.captures("[[wiki1:Some Page]]").unwrap();
+        assert_eq!(&cap["path"], "Some Page");
+        assert_eq!(&cap["prefix"], "wiki1");
+    }
+    #[test]
+    fn it_capture_reference_style_link_definition() {
+        let cap = MD_LINK_REF_RE.captures("[id]: path/to/page").unwrap();
+        assert_eq!(&cap["path"], "path/to/page");
+        assert!(&cap.name("prefix").is_none());
+
+        let cap = MD_LINK_REF_RE
+            .captures("[id]: diary:2010-01-01")
+            .unwrap();
+        assert_eq!(&cap["path"], "2010-01-01");
+        assert_eq!(&cap["prefix"], "diary");
+    }
+    #[test]
+    fn it_captures_reference_style_link_definition_with_title() {
+        let cap = MD_LINK_REF_RE
+            .captures("[id]: path/to/page \"Title\"")
+            .unwrap();
+        assert_eq!(&cap["path"], "path/to/page");
+        assert_eq!(&cap["right"], " \"Title\"");
+    }
+    #[test]
+    fn it_ignores_reference_style_label_usage() {
+        assert!(!MD_LINK_REF_RE.is_match("See [text][id] for more."));
+        assert!(!MD_LINK_REF_RE.is_match("See [id] for more."));
+    }
+    #[test]
+    fn it_capture_named_interwiki_link() {
+        let cap = DEFAULT_LINK_RE
+            .captures("[[wn.MyWiki:Some Page]]")
+            .unwrap();
+        assert_eq!(&cap["path"], "Some Page");
+        assert_eq!(&cap["prefix"], "wn.MyWiki");
+    }
 }
 
 #[cfg(test)]
@@ -327,11 +855,12 @@ mod tests {
     lazy_static! {
         static ref WIKI_ROOT: PathBuf = PathBuf::from("/dropbox/vimwiki");
         static ref CONTENT_PATH: PathBuf = PathBuf::from("/dropbox/vimwiki/books/note.md");
+        static ref EMPTY_REGISTRY: WikiRegistry = WikiRegistry::new();
     }
 
     #[test]
     fn it_replace_diary_links() {
-        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &EMPTY_REGISTRY);
         let content = r#"
         Here is a [diary](diary:2010-01-01).
         "#;
@@ -349,7 +878,7 @@ mod tests {
 
     #[test]
     fn it_replace_diary_links_to_non_dairy() {
-        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &EMPTY_REGISTRY);
         let content = r#"
         Here is a [diary](diary:2010-01-01).
         "#;
@@ -367,7 +896,7 @@ mod tests {
 
     #[test]
     fn it_replace_absolute_link() {
-        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &EMPTY_REGISTRY);
         let content = r#"
         Here is a [absolute to root](/link).
         "#;
@@ -385,7 +914,7 @@ mod tests {
 
     #[test]
     fn it_replace_all_matched_links() {
-        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &EMPTY_REGISTRY);
         let content = r#"
         - [local link relative link](local:./link).
         - [file link](file:link).
@@ -408,4 +937,356 @@ mod tests {
         "#
         );
     }
+
+    #[test]
+    fn it_replace_interwiki_link_by_index() {
+        let mut registry = WikiRegistry::new();
+        registry.register(None, "/dropbox/vimwiki", "md", false);
+        registry.register(None, "/dropbox/work-wiki", "md", false);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &registry);
+        let content = r#"
+        Here is a [[wiki1:Some Page]].
+        "#;
+        assert_eq!(
+            wiki.replace_links(
+                content,
+                &AbsolutePath::new("/dropbox/work-wiki/Some Page.md"),
+                &AbsolutePath::new("/dropbox/work-wiki/moved/Some Page.md")
+            ),
+            r#"
+        Here is a [[wiki1:moved/Some Page]].
+        "#
+        );
+    }
+
+    #[test]
+    fn it_emits_interwiki_link_when_rename_crosses_wiki_roots() {
+        let mut registry = WikiRegistry::new();
+        registry.register(None, "/dropbox/vimwiki", "md", false);
+        registry.register(Some("Work"), "/dropbox/work-wiki", "md", false);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &registry);
+        let content = r#"
+        Here is a [local link](./moved-elsewhere).
+        "#;
+        assert_eq!(
+            wiki.replace_links(
+                content,
+                &AbsolutePath::new("/dropbox/vimwiki/books/moved-elsewhere.md"),
+                &AbsolutePath::new("/dropbox/work-wiki/archive/moved-elsewhere.md")
+            ),
+            r#"
+        Here is a [local link](wn.Work:archive/moved-elsewhere).
+        "#
+        );
+    }
+
+    #[test]
+    fn it_drops_interwiki_prefix_when_rename_moves_into_local_wiki() {
+        let mut registry = WikiRegistry::new();
+        registry.register(None, "/dropbox/vimwiki", "md", false);
+        registry.register(Some("Work"), "/dropbox/work-wiki", "md", false);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &registry);
+        let content = r#"
+        Here is a [[wn.Work:Page]].
+        "#;
+        assert_eq!(
+            wiki.replace_links(
+                content,
+                &AbsolutePath::new("/dropbox/work-wiki/Page.md"),
+                &AbsolutePath::new("/dropbox/vimwiki/moved/Page.md")
+            ),
+            r#"
+        Here is a [[../moved/Page]].
+        "#
+        );
+    }
+
+    #[test]
+    fn it_keeps_extension_for_wikis_using_markdown_link_style() {
+        let mut registry = WikiRegistry::new();
+        registry.register(None, "/dropbox/vimwiki", "md", true);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &registry);
+        let content = r#"
+        Here is a [local link](./link).
+        "#;
+        assert_eq!(
+            wiki.replace_links(
+                content,
+                &AbsolutePath::new("/dropbox/vimwiki/books/link.md"),
+                &AbsolutePath::new("/dropbox/vimwiki/books/renamed.md")
+            ),
+            r#"
+        Here is a [local link](renamed.md).
+        "#
+        );
+    }
+
+    #[test]
+    fn it_matches_bare_link_only_against_known_extensions() {
+        let mut registry = WikiRegistry::new();
+        registry.register(None, "/dropbox/vimwiki", "md", false);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &registry);
+        let content = r#"
+        Here is a [link](link).
+        "#;
+        assert_eq!(
+            wiki.replace_links(
+                content,
+                &AbsolutePath::new("/dropbox/vimwiki/books/link.wiki"),
+                &AbsolutePath::new("/dropbox/vimwiki/books/renamed.wiki")
+            ),
+            content,
+            "a bare link shouldn't match a file whose extension isn't known to the registry"
+        );
+    }
+
+    #[test]
+    fn it_resolves_diary_file_name_using_diary_wikis_own_extension() {
+        let mut registry = WikiRegistry::new();
+        registry.register(None, "/dropbox/vimwiki", "wiki", false);
+        registry.register(None, "/dropbox/vimwiki/diary", "md", true);
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &registry);
+        let content = r#"
+        Here is a [diary](diary:2010-01-01).
+        "#;
+        assert_eq!(
+            wiki.replace_links(
+                content,
+                &AbsolutePath::new("/dropbox/vimwiki/diary/2010-01-01.md"),
+                &AbsolutePath::new("/dropbox/vimwiki/diary/2020-02-02.md")
+            ),
+            r#"
+        Here is a [diary](diary:2020-02-02.md).
+        "#
+        );
+    }
+
+    #[test]
+    fn it_skips_external_links_when_checking() {
+        assert!(is_external_link("http://example.com/page"));
+        assert!(is_external_link("https://example.com/page"));
+        assert!(!is_external_link("books/link"));
+    }
+
+    #[test]
+    fn it_finds_broken_links() {
+        let dir = std::env::temp_dir().join("vimwiki_rename_link_check_test");
+        fs::create_dir_all(&dir).unwrap();
+        let content_path = dir.join("note.md");
+        fs::write(&content_path, "Here is a [[missing page]].").unwrap();
+
+        let registry = WikiRegistry::new();
+        let wiki = Wiki::new(&dir, &content_path, &registry);
+        let broken = wiki.check_links().unwrap();
+
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].source_file, content_path);
+        assert_eq!(broken[0].raw_link, "[[missing page]]");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_does_not_report_existing_extensionless_targets_as_broken() {
+        let dir = std::env::temp_dir().join("vimwiki_rename_link_extensionless_check_test");
+        fs::create_dir_all(&dir).unwrap();
+        let content_path = dir.join("note.md");
+        fs::write(&content_path, "Here is a [[target]].").unwrap();
+        fs::write(dir.join("target"), "the page itself").unwrap();
+
+        let registry = WikiRegistry::new();
+        let wiki = Wiki::new(&dir, &content_path, &registry);
+        let broken = wiki.check_links().unwrap();
+
+        assert!(broken.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_does_not_report_existing_extensioned_targets_as_broken_with_no_registry() {
+        let dir = std::env::temp_dir().join("vimwiki_rename_link_no_registry_check_test");
+        fs::create_dir_all(&dir).unwrap();
+        let content_path = dir.join("note.md");
+        fs::write(&content_path, "Here is a [[target]].").unwrap();
+        fs::write(dir.join("target.md"), "the page itself").unwrap();
+
+        let registry = WikiRegistry::new();
+        let wiki = Wiki::new(&dir, &content_path, &registry);
+        let broken = wiki.check_links().unwrap();
+
+        assert!(broken.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_finds_backlinks_and_renames_only_referencing_files() {
+        let dir = std::env::temp_dir().join("vimwiki_rename_link_backlinks_test");
+        fs::create_dir_all(&dir).unwrap();
+        let linking = dir.join("linking.md");
+        let unrelated = dir.join("unrelated.md");
+        let target = dir.join("target");
+        fs::write(&linking, "See [[target]] for more.").unwrap();
+        fs::write(&unrelated, "Nothing to see here.").unwrap();
+        fs::write(&target, "the page itself").unwrap();
+
+        let registry = WikiRegistry::new();
+        let found = backlinks(&dir, "target", &registry);
+        assert_eq!(found, vec![linking.clone()]);
+
+        rename(
+            dir.clone(),
+            target.to_str().unwrap(),
+            dir.join("renamed").to_str().unwrap(),
+            &registry,
+            &RenameOptions::default(),
+        );
+        assert!(!target.exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("renamed")).unwrap(),
+            "the page itself"
+        );
+        assert_eq!(
+            fs::read_to_string(&linking).unwrap(),
+            "See [[renamed]] for more."
+        );
+        assert_eq!(
+            fs::read_to_string(&unrelated).unwrap(),
+            "Nothing to see here."
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_previews_rename_without_touching_disk() {
+        let dir = std::env::temp_dir().join("vimwiki_rename_link_dry_run_test");
+        fs::create_dir_all(&dir).unwrap();
+        let linking = dir.join("linking.md");
+        let unrelated = dir.join("unrelated.md");
+        let target = dir.join("target");
+        fs::write(&linking, "See [[target]] for more.").unwrap();
+        fs::write(&unrelated, "Nothing to see here.").unwrap();
+        fs::write(&target, "the page itself").unwrap();
+
+        let registry = WikiRegistry::new();
+        let diffs = rename(
+            dir.clone(),
+            target.to_str().unwrap(),
+            dir.join("renamed").to_str().unwrap(),
+            &registry,
+            &RenameOptions {
+                dry_run: true,
+                ..RenameOptions::default()
+            },
+        );
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].source_file, linking);
+        assert_eq!(diffs[0].changes.len(), 1);
+        assert_eq!(diffs[0].changes[0].old_line, "See [[target]] for more.");
+        assert_eq!(diffs[0].changes[0].new_line, "See [[renamed]] for more.");
+
+        assert!(target.exists());
+        assert_eq!(fs::read_to_string(&linking).unwrap(), "See [[target]] for more.");
+        assert_eq!(
+            fs::read_to_string(&unrelated).unwrap(),
+            "Nothing to see here."
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_replace_reference_style_link_definition() {
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &EMPTY_REGISTRY);
+        let content = "See [a link][1] for more.\n\n[1]: ./link\n";
+        assert_eq!(
+            wiki.replace_links(
+                content,
+                &AbsolutePath::new("/dropbox/vimwiki/books/link.md"),
+                &AbsolutePath::new("/dropbox/vimwiki/books/renamed.md")
+            ),
+            "See [a link][1] for more.\n\n[1]: renamed\n"
+        );
+    }
+
+    #[test]
+    fn it_replace_reference_style_link_definition_with_title() {
+        let wiki = Wiki::new(&WIKI_ROOT, &CONTENT_PATH, &EMPTY_REGISTRY);
+        let content = "See [a link][1] for more.\n\n[1]: ./link \"Title\"\n";
+        assert_eq!(
+            wiki.replace_links(
+                content,
+                &AbsolutePath::new("/dropbox/vimwiki/books/link.md"),
+                &AbsolutePath::new("/dropbox/vimwiki/books/renamed.md")
+            ),
+            "See [a link][1] for more.\n\n[1]: renamed \"Title\"\n"
+        );
+    }
+
+    #[test]
+    fn it_renames_with_git_mv_and_stages_edits() {
+        let dir = std::env::temp_dir().join("vimwiki_rename_link_git_test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        Command::new("git").arg("init").current_dir(&dir).status().unwrap();
+        Command::new("git")
+            .args(["config", "user.email", "test@example.com"])
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        Command::new("git")
+            .args(["config", "user.name", "Test"])
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+
+        let linking = dir.join("linking.md");
+        let target = dir.join("target.md");
+        fs::write(&linking, "See [[target]] for more.").unwrap();
+        fs::write(&target, "the page itself").unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "initial"])
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+
+        let registry = WikiRegistry::new();
+        rename(
+            dir.clone(),
+            target.to_str().unwrap(),
+            dir.join("renamed.md").to_str().unwrap(),
+            &registry,
+            &RenameOptions {
+                use_git: true,
+                ..RenameOptions::default()
+            },
+        );
+
+        assert!(!target.exists());
+        assert!(dir.join("renamed.md").exists());
+
+        let status = Command::new("git")
+            .args(["status", "--porcelain"])
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        let status = String::from_utf8(status.stdout).unwrap();
+        // A clean `git add`-staged changeset: the move and the rewritten
+        // link both show up as staged (`R`/`M` in the index column), with
+        // nothing left in the working tree column.
+        for line in status.lines() {
+            assert_ne!(&line[0..1], " ", "expected {} to be staged", line);
+            assert_eq!(&line[1..2], " ", "expected {} to have no unstaged changes", line);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }